@@ -0,0 +1,219 @@
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Signed, versioned membership document for private-network relays.
+//!
+//! Modeled on Tor's consensus/netdir: a monotonically versioned list of trusted
+//! [`RouterId`]s — optionally carrying per-relay role flags and advertised
+//! bandwidth — signed by one or more network-authority keys. This lets private
+//! network operators roll out relay-set changes network-wide with authenticity
+//! and replay protection, and is the source the hot-reload path consumes for
+//! pushed updates.
+
+use crate::{crypto::SigningPublicKey, primitives::RouterId};
+
+/// Logging target for the file.
+const LOG_TARGET: &str = "emissary::private_network::membership";
+
+/// Role flag marking a relay as a floodfill participant.
+pub const ROLE_FLOODFILL: u8 = 0x01;
+
+/// A single relay entry within a membership document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MembershipEntry {
+    /// Router ID of the relay.
+    pub id: RouterId,
+
+    /// Role flags for the relay (see [`ROLE_FLOODFILL`]).
+    pub roles: u8,
+
+    /// Advertised bandwidth in KBps, if declared.
+    pub bandwidth: Option<u32>,
+}
+
+impl MembershipEntry {
+    /// Whether the relay is declared as a floodfill.
+    pub fn is_floodfill(&self) -> bool {
+        self.roles & ROLE_FLOODFILL != 0
+    }
+}
+
+/// Errors returned while loading or verifying a membership document.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MembershipError {
+    /// The document could not be parsed.
+    Malformed,
+
+    /// The document's version is not newer than the one already held.
+    StaleVersion,
+
+    /// No authority signature over the document was valid.
+    InvalidSignature,
+}
+
+/// A signed, versioned list of trusted private-network relays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateNetMembership {
+    /// Monotonically increasing document version.
+    version: u64,
+
+    /// Trusted relays, sorted by router ID for a canonical encoding.
+    relays: Vec<MembershipEntry>,
+
+    /// Authority signatures over the canonical document bytes.
+    signatures: Vec<Vec<u8>>,
+}
+
+impl PrivateNetMembership {
+    /// Create a new, unsigned membership document.
+    ///
+    /// Relays are stored in canonical (router-ID) order so the signed bytes are
+    /// deterministic regardless of insertion order.
+    pub fn new(version: u64, mut relays: Vec<MembershipEntry>) -> Self {
+        relays.sort_by(|a, b| a.id.cmp(&b.id));
+        Self {
+            version,
+            relays,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Document version.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The trusted relays declared by the document.
+    pub fn relays(&self) -> &[MembershipEntry] {
+        &self.relays
+    }
+
+    /// Attach an authority signature produced over [`signing_bytes`](Self::signing_bytes).
+    pub fn add_signature(&mut self, signature: Vec<u8>) {
+        self.signatures.push(signature);
+    }
+
+    /// Canonical byte encoding signed by the network authorities.
+    ///
+    /// Layout: the 8-byte big-endian version, followed by each relay as its
+    /// 32-byte router ID, a role byte, and a bandwidth field (a presence byte
+    /// plus a 4-byte big-endian value when present).
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8 + self.relays.len() * 38);
+        out.extend_from_slice(&self.version.to_be_bytes());
+        for relay in &self.relays {
+            out.extend_from_slice(relay.id.as_ref());
+            out.push(relay.roles);
+            match relay.bandwidth {
+                Some(bandwidth) => {
+                    out.push(1);
+                    out.extend_from_slice(&bandwidth.to_be_bytes());
+                }
+                None => out.push(0),
+            }
+        }
+        out
+    }
+
+    /// Verify the document against the network-authority keys and a minimum
+    /// acceptable version.
+    ///
+    /// The document is rejected if its version is not strictly newer than
+    /// `current_version` (replay protection) or if none of the authority keys
+    /// produced a valid signature over its canonical bytes.
+    pub fn verify(
+        &self,
+        authorities: &[SigningPublicKey],
+        current_version: u64,
+    ) -> Result<(), MembershipError> {
+        if self.version <= current_version {
+            tracing::debug!(
+                target: LOG_TARGET,
+                version = self.version,
+                current_version,
+                "rejecting membership document with stale version",
+            );
+            return Err(MembershipError::StaleVersion);
+        }
+
+        let message = self.signing_bytes();
+        let signed_by_authority = self.signatures.iter().any(|signature| {
+            authorities
+                .iter()
+                .any(|authority| authority.verify(&message, signature).is_ok())
+        });
+
+        if !signed_by_authority {
+            tracing::debug!(
+                target: LOG_TARGET,
+                version = self.version,
+                "rejecting membership document with no valid authority signature",
+            );
+            return Err(MembershipError::InvalidSignature);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(bandwidth: Option<u32>, roles: u8) -> MembershipEntry {
+        MembershipEntry {
+            id: RouterId::random(),
+            roles,
+            bandwidth,
+        }
+    }
+
+    #[test]
+    fn stale_version_is_rejected() {
+        let doc = PrivateNetMembership::new(5, vec![entry(Some(256), 0)]);
+        assert_eq!(doc.verify(&[], 5), Err(MembershipError::StaleVersion));
+        assert_eq!(doc.verify(&[], 6), Err(MembershipError::StaleVersion));
+    }
+
+    #[test]
+    fn unsigned_document_is_rejected() {
+        // Newer version, but no authority signature is present.
+        let doc = PrivateNetMembership::new(6, vec![entry(Some(256), 0)]);
+        assert_eq!(doc.verify(&[], 5), Err(MembershipError::InvalidSignature));
+    }
+
+    #[test]
+    fn relays_are_stored_in_canonical_order() {
+        let a = entry(None, 0);
+        let b = entry(None, ROLE_FLOODFILL);
+        let doc = PrivateNetMembership::new(1, vec![a.clone(), b.clone()]);
+
+        let ordered = doc.relays().windows(2).all(|pair| pair[0].id <= pair[1].id);
+        assert!(ordered);
+        assert_eq!(doc.relays().len(), 2);
+    }
+
+    #[test]
+    fn signing_bytes_reflect_version_and_relays() {
+        let doc = PrivateNetMembership::new(7, vec![entry(Some(2048), ROLE_FLOODFILL)]);
+        let bytes = doc.signing_bytes();
+        // 8-byte version + 32-byte id + role byte + presence byte + 4-byte bandwidth.
+        assert_eq!(bytes.len(), 8 + 32 + 1 + 1 + 4);
+        assert_eq!(&bytes[..8], &7u64.to_be_bytes());
+    }
+}
@@ -0,0 +1,1013 @@
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Private network validation and management.
+
+mod guard;
+mod membership;
+mod stats;
+
+pub use guard::{GuardConfig, GuardSet, PersistedGuard};
+pub use membership::{
+    MembershipEntry, MembershipError, PrivateNetMembership, ROLE_FLOODFILL,
+};
+pub use stats::RelayStats;
+
+use crate::{
+    config::PrivateNetworkConfig,
+    primitives::{RouterId, RouterInfo},
+    crypto::base64_decode,
+};
+
+use hashbrown::{HashMap, HashSet};
+use rand::Rng;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use core::net::IpAddr;
+
+/// Logging target for the file.
+const LOG_TARGET: &str = "emissary::private_network";
+
+/// Event emitted when the trusted relay set changes.
+///
+/// Subsystems such as the router manager can react to these to proactively tear
+/// down tunnels and routing-table entries for a revoked relay rather than
+/// waiting for the next validation call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivateNetworkEvent {
+    /// A relay was added to the trusted set.
+    RelayAdmitted(RouterId),
+
+    /// A relay was removed from the trusted set.
+    RelayRevoked(RouterId),
+}
+
+/// Private network validator.
+#[derive(Debug, Clone)]
+pub struct PrivateNetworkValidator {
+    /// Whether private network mode is enabled.
+    enabled: bool,
+    
+    /// Set of known relay router IDs.
+    known_relays: HashSet<RouterId>,
+
+    /// Bandwidth-derived selection weight per known relay.
+    ///
+    /// Populated lazily as router infos are observed; relays with no recorded
+    /// weight fall back to the base tier factor of `1.0`.
+    relay_weights: HashMap<RouterId, f64>,
+
+    /// Minimum bandwidth requirement for known relays.
+    min_bandwidth: Option<String>,
+
+    /// Per-relay reliability scoring.
+    stats: RelayStats,
+
+    /// Minimum reliability a relay must retain to remain admissible.
+    min_reliability: f64,
+
+    /// Optional channel for admission/revocation events.
+    event_tx: Option<UnboundedSender<PrivateNetworkEvent>>,
+}
+
+/// Minimum reliability a relay must retain to be used as a tunnel hop or
+/// floodfill. Relays that have never been observed read as fully reliable, so
+/// this only excludes relays with a demonstrated failure history.
+const DEFAULT_MIN_RELIABILITY: f64 = 0.2;
+
+/// Minimum advertised throughput, in KBps, implied by each I2P capability tier.
+///
+/// These are the documented tier floors: a relay declaring a given tier
+/// advertises at least this much outbound bandwidth. `K` is the sub-12 KBps
+/// tier and so floors at `0`; `X` is the open-ended top tier and is represented
+/// by the start of its range.
+fn tier_bandwidth_floor(tier: char) -> Option<u32> {
+    Some(match tier {
+        'K' => 0,
+        'L' => 12,
+        'M' => 48,
+        'N' => 64,
+        'O' => 128,
+        'P' => 256,
+        'X' => 2048,
+        _ => return None,
+    })
+}
+
+/// Coarse selection-weight factor for a capability tier, used when a relay
+/// advertises no numeric bandwidth.
+///
+/// Mirrors Tor's consensus weighting: the `K`/`L`/`M`/`N` tiers share the base
+/// factor of `1.0` while the faster `O`/`P`/`X` tiers are weighted
+/// progressively higher so capacity tracks selection probability.
+fn tier_weight(tier: char) -> f64 {
+    match tier {
+        'O' => 4.0,
+        'P' => 8.0,
+        'X' => 16.0,
+        _ => 1.0,
+    }
+}
+
+impl PrivateNetworkValidator {
+    /// Create a new private network validator.
+    pub fn new(config: Option<&PrivateNetworkConfig>) -> Self {
+        match config {
+            Some(config) if config.enabled => {
+                let known_relays = Self::parse_known_relays(config);
+
+                tracing::info!(
+                    target: LOG_TARGET,
+                    known_relays_count = known_relays.len(),
+                    "private network mode enabled with known relays"
+                );
+
+                Self {
+                    enabled: true,
+                    known_relays,
+                    relay_weights: HashMap::new(),
+                    min_bandwidth: config.min_bandwidth.clone(),
+                    stats: RelayStats::default(),
+                    min_reliability: DEFAULT_MIN_RELIABILITY,
+                    event_tx: None,
+                }
+            }
+            _ => Self {
+                enabled: false,
+                known_relays: HashSet::new(),
+                relay_weights: HashMap::new(),
+                min_bandwidth: None,
+                stats: RelayStats::default(),
+                min_reliability: DEFAULT_MIN_RELIABILITY,
+                event_tx: None,
+            },
+        }
+    }
+
+    /// Build a validator from a verified membership document.
+    ///
+    /// The caller is responsible for verifying the document (see
+    /// [`PrivateNetMembership::verify`]) before handing it here; the trusted set
+    /// and per-relay bandwidth weights are taken directly from its entries. This
+    /// is the authenticated alternative to the inline config accepted by
+    /// [`new`](Self::new).
+    pub fn from_membership(membership: &PrivateNetMembership) -> Self {
+        let mut known_relays = HashSet::new();
+        let mut relay_weights = HashMap::new();
+        for entry in membership.relays() {
+            known_relays.insert(entry.id);
+            if let Some(bandwidth) = entry.bandwidth {
+                relay_weights.insert(entry.id, bandwidth as f64);
+            }
+        }
+
+        tracing::info!(
+            target: LOG_TARGET,
+            version = membership.version(),
+            known_relays_count = known_relays.len(),
+            "private network mode enabled from membership document"
+        );
+
+        Self {
+            enabled: true,
+            known_relays,
+            relay_weights,
+            min_bandwidth: None,
+            stats: RelayStats::default(),
+            min_reliability: DEFAULT_MIN_RELIABILITY,
+            event_tx: None,
+        }
+    }
+
+    /// Parse the base64-encoded known-relay router IDs from a config.
+    fn parse_known_relays(config: &PrivateNetworkConfig) -> HashSet<RouterId> {
+        config
+            .known_relays
+            .iter()
+            .filter_map(|relay_str| {
+                // Parse router ID from string
+                // This assumes the string is base64 encoded router ID
+                base64_decode(relay_str.as_bytes()).and_then(|bytes| {
+                    if bytes.len() == 32 {
+                        let mut router_id = [0u8; 32];
+                        router_id.copy_from_slice(&bytes);
+                        Some(RouterId::from(router_id))
+                    } else {
+                        None
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Subscribe to relay admission/revocation events.
+    ///
+    /// Returns a receiver that yields a [`PrivateNetworkEvent`] whenever a relay
+    /// is added to or removed from the trusted set, letting subsystems react to
+    /// a hot-reloaded relay set. Only the most recent subscription is notified.
+    pub fn subscribe(&mut self) -> UnboundedReceiver<PrivateNetworkEvent> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        self.event_tx = Some(tx);
+        rx
+    }
+
+    /// Add a relay to the trusted set, emitting [`PrivateNetworkEvent::RelayAdmitted`].
+    ///
+    /// Returns `true` if the relay was newly admitted.
+    pub fn add_relay(&mut self, router_id: RouterId) -> bool {
+        if self.known_relays.insert(router_id) {
+            tracing::debug!(target: LOG_TARGET, %router_id, "relay admitted");
+            self.emit(PrivateNetworkEvent::RelayAdmitted(router_id));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove a relay from the trusted set, emitting [`PrivateNetworkEvent::RelayRevoked`].
+    ///
+    /// Once removed, the relay is immediately rejected by
+    /// [`can_be_tunnel_hop`](Self::can_be_tunnel_hop),
+    /// [`can_be_floodfill`](Self::can_be_floodfill) and
+    /// [`can_be_added_to_routing_table`](Self::can_be_added_to_routing_table).
+    /// Returns `true` if the relay was present.
+    pub fn remove_relay(&mut self, router_id: &RouterId) -> bool {
+        if self.known_relays.remove(router_id) {
+            self.relay_weights.remove(router_id);
+            tracing::debug!(target: LOG_TARGET, %router_id, "relay revoked");
+            self.emit(PrivateNetworkEvent::RelayRevoked(*router_id));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reload the trusted relay set from a new config, diffing against the
+    /// current set and emitting an event for each added or removed relay.
+    pub fn reload(&mut self, config: &PrivateNetworkConfig) {
+        let next = Self::parse_known_relays(config);
+
+        let revoked: Vec<RouterId> =
+            self.known_relays.difference(&next).copied().collect();
+        let admitted: Vec<RouterId> =
+            next.difference(&self.known_relays).copied().collect();
+
+        for router_id in revoked {
+            self.remove_relay(&router_id);
+        }
+        for router_id in admitted {
+            self.add_relay(router_id);
+        }
+
+        self.min_bandwidth = config.min_bandwidth.clone();
+        self.enabled = config.enabled;
+
+        tracing::info!(
+            target: LOG_TARGET,
+            known_relays_count = self.known_relays.len(),
+            "reloaded private network relay set"
+        );
+    }
+
+    /// Emit an event on the subscription channel, dropping it if no subscriber
+    /// is listening.
+    fn emit(&self, event: PrivateNetworkEvent) {
+        if let Some(tx) = &self.event_tx {
+            let _ = tx.send(event);
+        }
+    }
+
+    /// Check if private network mode is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Check if a router ID is a known relay.
+    pub fn is_known_relay(&self, router_id: &RouterId) -> bool {
+        self.known_relays.contains(router_id)
+    }
+
+    /// Check if a router can participate as a tunnel hop.
+    /// In private network mode, only known relays can be tunnel hops.
+    pub fn can_be_tunnel_hop(&self, router_id: &RouterId, router_info: &RouterInfo) -> bool {
+        if !self.enabled {
+            return true; // Normal I2P behavior when private network is disabled
+        }
+
+        // Only known relays can be tunnel hops
+        if !self.is_known_relay(router_id) {
+            tracing::debug!(
+                target: LOG_TARGET,
+                %router_id,
+                "router rejected as tunnel hop: not a known relay"
+            );
+            return false;
+        }
+
+        // Reject relays whose reliability has decayed below the threshold.
+        if self.stats.reliability(router_id) < self.min_reliability {
+            tracing::debug!(
+                target: LOG_TARGET,
+                %router_id,
+                "router rejected as tunnel hop: reliability below threshold"
+            );
+            return false;
+        }
+
+        // Check bandwidth requirements if specified
+        if let Some(min_bandwidth) = &self.min_bandwidth {
+            if !self.meets_bandwidth_requirement(router_info, min_bandwidth) {
+                tracing::debug!(
+                    target: LOG_TARGET,
+                    %router_id,
+                    min_bandwidth = %min_bandwidth,
+                    "router rejected as tunnel hop: insufficient bandwidth"
+                );
+                return false;
+            }
+        }
+
+        // Additional checks for private network
+        if !router_info.is_reachable() || !router_info.is_usable() {
+            tracing::debug!(
+                target: LOG_TARGET,
+                %router_id,
+                "router rejected as tunnel hop: not reachable or usable"
+            );
+            return false;
+        }
+
+        true
+    }
+
+    /// Check if a router can participate as a floodfill node.
+    /// In private network mode, only known relays can be floodfill nodes.
+    pub fn can_be_floodfill(&self, router_id: &RouterId, router_info: &RouterInfo) -> bool {
+        if !self.enabled {
+            return router_info.is_floodfill(); // Normal I2P behavior
+        }
+
+        // Only known relays can be floodfill nodes
+        if !self.is_known_relay(router_id) {
+            tracing::debug!(
+                target: LOG_TARGET,
+                %router_id,
+                "router rejected as floodfill: not a known relay"
+            );
+            return false;
+        }
+
+        // Reject relays whose reliability has decayed below the threshold.
+        if self.stats.reliability(router_id) < self.min_reliability {
+            tracing::debug!(
+                target: LOG_TARGET,
+                %router_id,
+                "router rejected as floodfill: reliability below threshold"
+            );
+            return false;
+        }
+
+        // Must have floodfill capability
+        if !router_info.is_floodfill() {
+            tracing::debug!(
+                target: LOG_TARGET,
+                %router_id,
+                "router rejected as floodfill: no floodfill capability"
+            );
+            return false;
+        }
+
+        // Check bandwidth requirements if specified
+        if let Some(min_bandwidth) = &self.min_bandwidth {
+            if !self.meets_bandwidth_requirement(router_info, min_bandwidth) {
+                tracing::debug!(
+                    target: LOG_TARGET,
+                    %router_id,
+                    min_bandwidth = %min_bandwidth,
+                    "router rejected as floodfill: insufficient bandwidth"
+                );
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Check if a router can be added to the routing table.
+    /// In private network mode, only known relays can be added.
+    pub fn can_be_added_to_routing_table(&self, router_id: &RouterId, router_info: &RouterInfo) -> bool {
+        if !self.enabled {
+            return true; // Normal I2P behavior
+        }
+
+        // Only known relays can be added to routing table
+        if !self.is_known_relay(router_id) {
+            tracing::debug!(
+                target: LOG_TARGET,
+                %router_id,
+                "router rejected from routing table: not a known relay"
+            );
+            return false;
+        }
+
+        // Must be reachable and usable
+        if !router_info.is_reachable() || !router_info.is_usable() {
+            tracing::debug!(
+                target: LOG_TARGET,
+                %router_id,
+                "router rejected from routing table: not reachable or usable"
+            );
+            return false;
+        }
+
+        true
+    }
+
+    /// Bandwidth-weighted random selection of up to `count` distinct tunnel hops.
+    ///
+    /// Mirrors Tor's consensus-weighted relay selection: every admissible known
+    /// relay (those not present in `exclude`) is assigned a numeric weight
+    /// derived from its advertised bandwidth, a cumulative-weight array is built
+    /// over the candidate set, and each draw picks a relay by sampling a uniform
+    /// value in `[0, total_weight)` and binary-searching the prefix sums. The
+    /// chosen relay is removed and its weight subtracted before the next draw so
+    /// the returned hops are distinct. Returns fewer than `count` entries when
+    /// the candidate pool is exhausted.
+    ///
+    /// Weights come from each relay's advertised bandwidth, learned either from a
+    /// membership document or by the router manager feeding observed router infos
+    /// through [`note_relay_info`](Self::note_relay_info); relays with no recorded
+    /// weight fall back to the base tier factor of `1.0`.
+    pub fn select_tunnel_hops(&self, count: usize, exclude: &HashSet<RouterId>) -> Vec<RouterId> {
+        let mut candidates: Vec<RouterId> = self
+            .known_relays
+            .iter()
+            .filter(|id| !exclude.contains(id))
+            .copied()
+            .collect();
+        let mut weights: Vec<f64> = candidates.iter().map(|id| self.relay_weight(id)).collect();
+
+        let mut selected = Vec::with_capacity(count.min(candidates.len()));
+        let mut rng = rand::thread_rng();
+
+        while selected.len() < count && !candidates.is_empty() {
+            // Build the cumulative-weight array over the remaining candidates.
+            let mut prefix = Vec::with_capacity(weights.len());
+            let mut total = 0.0;
+            for &w in &weights {
+                total += w;
+                prefix.push(total);
+            }
+
+            if total <= 0.0 {
+                break;
+            }
+
+            // Sample a uniform value and binary-search the prefix sums.
+            let target = rng.gen_range(0.0..total);
+            let idx = prefix.partition_point(|&p| p <= target);
+
+            selected.push(candidates.swap_remove(idx));
+            weights.swap_remove(idx);
+        }
+
+        selected
+    }
+
+    /// Bandwidth-weighted hop selection that also enforces path diversity.
+    ///
+    /// Behaves like [`select_tunnel_hops`](Self::select_tunnel_hops) but consults
+    /// [`can_coexist_in_path`](Self::can_coexist_in_path) against the hops already
+    /// drawn, using the supplied `infos` map to resolve each candidate's
+    /// [`RouterInfo`]. Candidates with no known info, or that are too close to an
+    /// already-chosen hop, are dropped from the pool.
+    pub fn select_diverse_tunnel_hops<'a>(
+        &self,
+        count: usize,
+        exclude: &HashSet<RouterId>,
+        infos: &'a HashMap<RouterId, RouterInfo>,
+    ) -> Vec<RouterId> {
+        let mut candidates: Vec<RouterId> = self
+            .known_relays
+            .iter()
+            .filter(|id| !exclude.contains(id) && infos.contains_key(id))
+            .copied()
+            .collect();
+        let mut weights: Vec<f64> = candidates.iter().map(|id| self.relay_weight(id)).collect();
+
+        let mut selected = Vec::with_capacity(count.min(candidates.len()));
+        let mut chosen_infos: Vec<&'a RouterInfo> = Vec::with_capacity(selected.capacity());
+        let mut rng = rand::thread_rng();
+
+        while selected.len() < count && !candidates.is_empty() {
+            let mut prefix = Vec::with_capacity(weights.len());
+            let mut total = 0.0;
+            for &w in &weights {
+                total += w;
+                prefix.push(total);
+            }
+
+            if total <= 0.0 {
+                break;
+            }
+
+            let target = rng.gen_range(0.0..total);
+            let idx = prefix.partition_point(|&p| p <= target);
+
+            let id = candidates.swap_remove(idx);
+            weights.swap_remove(idx);
+
+            // `infos` is guaranteed to contain the id by the filter above.
+            let info = &infos[&id];
+            if self.can_coexist_in_path(info, &chosen_infos) {
+                chosen_infos.push(info);
+                selected.push(id);
+            }
+        }
+
+        selected
+    }
+
+    /// Whether `candidate` may share a tunnel path with the `already_chosen` hops.
+    ///
+    /// Borrows Tor's `RelayExclusion` notion of path diversity: a relay is
+    /// rejected when it is "too close" to a hop already selected for the same
+    /// tunnel. Two relays are considered too close when they share an IPv4 `/16`
+    /// (or IPv6 `/32`) network, or when they declare the same family/options
+    /// group. This keeps multi-hop tunnels from routing through two relays in the
+    /// same network neighborhood — a real risk in a small private network where
+    /// one operator may run several relays on a single host.
+    pub fn can_coexist_in_path(
+        &self,
+        candidate: &RouterInfo,
+        already_chosen: &[&RouterInfo],
+    ) -> bool {
+        let candidate_ips = Self::extract_ips(candidate);
+        let candidate_family = Self::extract_family(candidate);
+
+        for chosen in already_chosen {
+            // Reject relays that share a network neighborhood with a chosen hop.
+            for ip in &candidate_ips {
+                if Self::extract_ips(chosen).iter().any(|other| Self::share_network(ip, other)) {
+                    return false;
+                }
+            }
+
+            // Reject relays that advertise the same family/options group.
+            if let (Some(a), Some(b)) = (&candidate_family, &Self::extract_family(chosen)) {
+                if a == b {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Collect the advertised IP addresses of a router from its transport addresses.
+    fn extract_ips(router_info: &RouterInfo) -> Vec<IpAddr> {
+        router_info
+            .addresses
+            .values()
+            .filter_map(|address| address.socket_address.map(|socket| socket.ip()))
+            .collect()
+    }
+
+    /// Extract the declared family/options group of a router, if any.
+    fn extract_family(router_info: &RouterInfo) -> Option<String> {
+        router_info
+            .options
+            .get("family")
+            .map(|family| family.to_string())
+    }
+
+    /// Whether two addresses fall within the same IPv4 `/16` or IPv6 `/32`.
+    fn share_network(a: &IpAddr, b: &IpAddr) -> bool {
+        match (a, b) {
+            (IpAddr::V4(a), IpAddr::V4(b)) => a.octets()[..2] == b.octets()[..2],
+            (IpAddr::V6(a), IpAddr::V6(b)) => a.octets()[..4] == b.octets()[..4],
+            _ => false,
+        }
+    }
+
+    /// Selection weight for a relay, falling back to the base tier factor.
+    ///
+    /// The bandwidth-derived weight is scaled by the relay's current reliability
+    /// so flaky-but-known relays are drawn less often.
+    fn relay_weight(&self, router_id: &RouterId) -> f64 {
+        let bandwidth = self.relay_weights.get(router_id).copied().unwrap_or(1.0);
+        bandwidth * self.stats.reliability(router_id)
+    }
+
+    /// Record the outcome of a tunnel build or message involving `router_id`.
+    pub fn record_outcome(&mut self, router_id: RouterId, success: bool) {
+        self.stats.record_outcome(router_id, success);
+    }
+
+    /// Current reliability of a relay in `[0, 1]`.
+    pub fn reliability(&self, router_id: &RouterId) -> f64 {
+        self.stats.reliability(router_id)
+    }
+
+    /// Record the bandwidth-derived selection weight for a known relay.
+    ///
+    /// Callers that observe a relay's [`RouterInfo`] can register it here so
+    /// subsequent [`select_tunnel_hops`](Self::select_tunnel_hops) draws are
+    /// weighted by advertised capacity rather than uniformly.
+    pub fn note_relay_weight(&mut self, router_id: RouterId, weight: f64) {
+        if self.known_relays.contains(&router_id) {
+            self.relay_weights.insert(router_id, weight);
+        }
+    }
+
+    /// Derive and record a known relay's selection weight from its [`RouterInfo`].
+    ///
+    /// This is how the config-built validator learns per-relay capacity: as the
+    /// router manager observes each relay's router info it feeds it here, so
+    /// [`select_tunnel_hops`](Self::select_tunnel_hops) weights by advertised
+    /// bandwidth instead of falling back to uniform selection. The weight is the
+    /// numeric bandwidth advertised in the router options when present, else the
+    /// tier factor (`K`/`L`/`M`/`N` = 1, `O`/`P`/`X` progressively higher).
+    pub fn note_relay_info(&mut self, router_id: RouterId, router_info: &RouterInfo) {
+        if self.known_relays.contains(&router_id) {
+            self.relay_weights
+                .insert(router_id, Self::bandwidth_weight(router_info));
+        }
+    }
+
+    /// Bandwidth-derived selection weight for a relay's router info.
+    fn bandwidth_weight(router_info: &RouterInfo) -> f64 {
+        let numeric = router_info
+            .options
+            .get("bandwidth")
+            .and_then(|value| value.to_string().trim().parse::<u32>().ok());
+
+        match numeric {
+            Some(kbps) if kbps > 0 => kbps as f64,
+            _ => Self::capability_tier(router_info)
+                .map(tier_weight)
+                .unwrap_or(1.0),
+        }
+    }
+
+    /// Check if a router meets the minimum bandwidth requirement.
+    ///
+    /// `min_bandwidth` may be either a capability tier letter (`"O"`, `"P"`, …)
+    /// for backward compatibility or a numeric KBps floor (e.g. `"2048"`). The
+    /// relay's effective bandwidth is the greater of the floor implied by its
+    /// advertised capability tier and any numeric value it publishes in its
+    /// router options; the relay passes when that effective bandwidth meets or
+    /// exceeds the requirement.
+    fn meets_bandwidth_requirement(&self, router_info: &RouterInfo, min_bandwidth: &str) -> bool {
+        let required = match Self::parse_bandwidth_floor(min_bandwidth) {
+            Some(required) => required,
+            None => {
+                tracing::warn!(
+                    target: LOG_TARGET,
+                    min_bandwidth = %min_bandwidth,
+                    "unknown minimum bandwidth requirement"
+                );
+                return false;
+            }
+        };
+
+        Self::advertised_bandwidth(router_info) >= required
+    }
+
+    /// Effective advertised bandwidth of a relay in KBps.
+    ///
+    /// Takes the greater of the floor implied by the capability tier and any
+    /// numeric `bandwidth` value carried in the router options.
+    fn advertised_bandwidth(router_info: &RouterInfo) -> u32 {
+        let tier_floor = Self::capability_tier(router_info)
+            .and_then(tier_bandwidth_floor)
+            .unwrap_or(0);
+        let numeric = router_info
+            .options
+            .get("bandwidth")
+            .and_then(|value| value.to_string().trim().parse::<u32>().ok())
+            .unwrap_or(0);
+
+        tier_floor.max(numeric)
+    }
+
+    /// Parse a minimum-bandwidth requirement into a KBps floor.
+    ///
+    /// Accepts a single tier letter or a numeric KBps string.
+    fn parse_bandwidth_floor(min_bandwidth: &str) -> Option<u32> {
+        let trimmed = min_bandwidth.trim();
+        if let Ok(kbps) = trimmed.parse::<u32>() {
+            return Some(kbps);
+        }
+
+        let mut chars = trimmed.chars();
+        match (chars.next(), chars.next()) {
+            (Some(tier), None) => tier_bandwidth_floor(tier),
+            _ => None,
+        }
+    }
+
+    /// Highest advertised bandwidth tier letter declared in a router's capabilities.
+    fn capability_tier(router_info: &RouterInfo) -> Option<char> {
+        router_info
+            .capabilities
+            .to_string()
+            .chars()
+            .filter(|c| matches!(c, 'K' | 'L' | 'M' | 'N' | 'O' | 'P' | 'X'))
+            .max_by_key(|c| tier_bandwidth_floor(*c).unwrap_or(0))
+    }
+
+    /// Get the list of known relay router IDs.
+    pub fn known_relays(&self) -> &HashSet<RouterId> {
+        &self.known_relays
+    }
+
+    /// Get the number of known relays.
+    pub fn known_relay_count(&self) -> usize {
+        self.known_relays.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{Capabilities, RouterIdentity},
+        runtime::mock::MockRuntime,
+    };
+    use hashbrown::HashMap;
+
+    fn create_test_router_info(caps: &str) -> RouterInfo {
+        let (identity, _, _) = RouterIdentity::random();
+        let capabilities = Capabilities::parse(&Str::from(caps)).unwrap();
+        
+        RouterInfo {
+            identity,
+            capabilities,
+            addresses: HashMap::new(),
+            net_id: 2,
+            options: crate::primitives::Mapping::default(),
+            published: crate::primitives::Date::new(0),
+        }
+    }
+
+    #[test]
+    fn private_network_disabled_allows_all() {
+        let validator = PrivateNetworkValidator::new(None);
+        let router_id = RouterId::random();
+        let router_info = create_test_router_info("LR");
+
+        assert!(!validator.is_enabled());
+        assert!(validator.can_be_tunnel_hop(&router_id, &router_info));
+        assert!(validator.can_be_added_to_routing_table(&router_id, &router_info));
+    }
+
+    #[test]
+    fn private_network_enabled_blocks_unknown_routers() {
+        let config = PrivateNetworkConfig {
+            enabled: true,
+            known_relays: vec!["test_relay_1".to_string()],
+            min_bandwidth: None,
+        };
+        
+        let validator = PrivateNetworkValidator::new(Some(&config));
+        let router_id = RouterId::random();
+        let router_info = create_test_router_info("LR");
+
+        assert!(validator.is_enabled());
+        assert!(!validator.can_be_tunnel_hop(&router_id, &router_info));
+        assert!(!validator.can_be_added_to_routing_table(&router_id, &router_info));
+    }
+
+    #[test]
+    fn bandwidth_requirement_enforcement() {
+        let config = PrivateNetworkConfig {
+            enabled: true,
+            known_relays: vec!["test_relay_1".to_string()],
+            min_bandwidth: Some("X".to_string()),
+        };
+        
+        let validator = PrivateNetworkValidator::new(Some(&config));
+        let router_id = RouterId::random();
+        
+        // Low bandwidth router should be rejected
+        let low_bw_router = create_test_router_info("LR");
+        assert!(!validator.can_be_tunnel_hop(&router_id, &low_bw_router));
+        
+        // High bandwidth router should be accepted
+        let high_bw_router = create_test_router_info("XR");
+        assert!(validator.can_be_tunnel_hop(&router_id, &high_bw_router));
+    }
+
+    /// Build an enabled validator with an explicit set of known relays.
+    fn validator_with_relays(relays: &[RouterId]) -> PrivateNetworkValidator {
+        PrivateNetworkValidator {
+            enabled: true,
+            known_relays: relays.iter().copied().collect(),
+            relay_weights: HashMap::new(),
+            min_bandwidth: None,
+            stats: RelayStats::default(),
+            min_reliability: DEFAULT_MIN_RELIABILITY,
+            event_tx: None,
+        }
+    }
+
+    #[test]
+    fn select_tunnel_hops_returns_distinct_relays() {
+        let relays: Vec<RouterId> = (0..5).map(|_| RouterId::random()).collect();
+        let validator = validator_with_relays(&relays);
+
+        let hops = validator.select_tunnel_hops(3, &HashSet::new());
+        assert_eq!(hops.len(), 3);
+
+        let unique: HashSet<RouterId> = hops.iter().copied().collect();
+        assert_eq!(unique.len(), 3);
+        assert!(hops.iter().all(|id| relays.contains(id)));
+    }
+
+    #[test]
+    fn note_relay_info_derives_weight_from_tier() {
+        let fast = RouterId::random();
+        let slow = RouterId::random();
+        let mut validator = validator_with_relays(&[fast, slow]);
+
+        // Before any info is observed, every relay falls back to the base weight.
+        assert_eq!(validator.relay_weight(&fast), validator.relay_weight(&slow));
+
+        validator.note_relay_info(fast, &create_test_router_info("XR"));
+        validator.note_relay_info(slow, &create_test_router_info("LR"));
+
+        // The faster tier is now weighted strictly higher.
+        assert!(validator.relay_weight(&fast) > validator.relay_weight(&slow));
+    }
+
+    #[test]
+    fn select_tunnel_hops_honours_exclude_and_pool_size() {
+        let relays: Vec<RouterId> = (0..3).map(|_| RouterId::random()).collect();
+        let validator = validator_with_relays(&relays);
+
+        // Excluding one relay leaves only two admissible candidates.
+        let mut exclude = HashSet::new();
+        exclude.insert(relays[0]);
+        let hops = validator.select_tunnel_hops(3, &exclude);
+        assert_eq!(hops.len(), 2);
+        assert!(!hops.contains(&relays[0]));
+    }
+
+    #[test]
+    fn shared_network_detection() {
+        use core::net::{Ipv4Addr, Ipv6Addr};
+
+        let a = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let b = IpAddr::V4(Ipv4Addr::new(10, 0, 255, 9));
+        let c = IpAddr::V4(Ipv4Addr::new(10, 1, 0, 1));
+        assert!(PrivateNetworkValidator::share_network(&a, &b)); // same /16
+        assert!(!PrivateNetworkValidator::share_network(&a, &c)); // different /16
+
+        let d = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+        let e = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0xffff, 0, 0, 0, 0, 2));
+        let f = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb9, 0, 0, 0, 0, 0, 1));
+        assert!(PrivateNetworkValidator::share_network(&d, &e)); // same /32
+        assert!(!PrivateNetworkValidator::share_network(&d, &f)); // different /32
+
+        // Mixed families never share a network.
+        assert!(!PrivateNetworkValidator::share_network(&a, &d));
+    }
+
+    #[test]
+    fn can_coexist_in_path_with_no_addresses() {
+        let validator = PrivateNetworkValidator::new(None);
+        let candidate = create_test_router_info("LR");
+        let chosen = create_test_router_info("LR");
+
+        // Routers that advertise no addresses or family are not "too close".
+        assert!(validator.can_coexist_in_path(&candidate, &[&chosen]));
+    }
+
+    #[test]
+    fn unreliable_relay_rejected_as_tunnel_hop() {
+        let id = RouterId::random();
+        let mut validator = validator_with_relays(&[id]);
+        let router_info = create_test_router_info("LR");
+
+        // A fresh known relay is admissible.
+        assert!(validator.can_be_tunnel_hop(&id, &router_info));
+
+        // After a run of failures its reliability drops below the threshold.
+        for _ in 0..20 {
+            validator.record_outcome(id, false);
+        }
+        assert!(!validator.can_be_tunnel_hop(&id, &router_info));
+    }
+
+    #[test]
+    fn numeric_bandwidth_threshold() {
+        let id = RouterId::random();
+        let mut validator = validator_with_relays(&[id]);
+        validator.min_bandwidth = Some("2048".to_string());
+
+        // Tier X floors at 2048 KBps and meets the numeric requirement.
+        assert!(validator.can_be_tunnel_hop(&id, &create_test_router_info("XR")));
+        // Tier P floors at 256 KBps and falls short.
+        assert!(!validator.can_be_tunnel_hop(&id, &create_test_router_info("PR")));
+    }
+
+    #[test]
+    fn tier_letters_are_ordered() {
+        let id = RouterId::random();
+        let mut validator = validator_with_relays(&[id]);
+        validator.min_bandwidth = Some("N".to_string());
+
+        // N floors at 64 KBps; O (128) clears it, L (12) does not.
+        assert!(validator.can_be_tunnel_hop(&id, &create_test_router_info("OR")));
+        assert!(!validator.can_be_tunnel_hop(&id, &create_test_router_info("LR")));
+    }
+
+    #[test]
+    fn add_and_remove_relay_emit_events() {
+        let existing = RouterId::random();
+        let mut validator = validator_with_relays(&[existing]);
+        let mut events = validator.subscribe();
+
+        let added = RouterId::random();
+        assert!(validator.add_relay(added));
+        assert!(validator.is_known_relay(&added));
+        assert!(!validator.add_relay(added)); // idempotent
+        assert_eq!(
+            events.try_recv(),
+            Ok(PrivateNetworkEvent::RelayAdmitted(added))
+        );
+
+        assert!(validator.remove_relay(&existing));
+        assert!(!validator.is_known_relay(&existing));
+        let router_info = create_test_router_info("XR");
+        assert!(!validator.can_be_tunnel_hop(&existing, &router_info));
+        assert_eq!(
+            events.try_recv(),
+            Ok(PrivateNetworkEvent::RelayRevoked(existing))
+        );
+    }
+
+    #[test]
+    fn reload_diffs_relay_set() {
+        let first = RouterId::random();
+        let second = RouterId::random();
+        let mut validator = validator_with_relays(&[first, second]);
+        let mut events = validator.subscribe();
+
+        // The new config lists no relays, so the reload revokes both current
+        // relays and emits a revocation event for each.
+        let config = PrivateNetworkConfig {
+            enabled: true,
+            known_relays: Vec::new(),
+            min_bandwidth: None,
+        };
+        validator.reload(&config);
+
+        assert!(!validator.is_known_relay(&first));
+        assert!(!validator.is_known_relay(&second));
+
+        let mut revoked = HashSet::new();
+        while let Ok(event) = events.try_recv() {
+            if let PrivateNetworkEvent::RelayRevoked(id) = event {
+                revoked.insert(id);
+            }
+        }
+        assert!(revoked.contains(&first));
+        assert!(revoked.contains(&second));
+    }
+
+    #[test]
+    fn validator_from_membership_trusts_declared_relays() {
+        let known = RouterId::random();
+        let membership = PrivateNetMembership::new(
+            1,
+            vec![MembershipEntry {
+                id: known,
+                roles: ROLE_FLOODFILL,
+                bandwidth: Some(2048),
+            }],
+        );
+
+        let validator = PrivateNetworkValidator::from_membership(&membership);
+        assert!(validator.is_enabled());
+        assert!(validator.is_known_relay(&known));
+        assert_eq!(validator.known_relay_count(), 1);
+        assert!(!validator.is_known_relay(&RouterId::random()));
+    }
+}
@@ -0,0 +1,178 @@
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Per-relay reliability scoring with exponential decay and expiry.
+//!
+//! Inspired by Veilid's route stats and Lightning's channel scoring: each
+//! tunnel-build or message outcome updates a decaying reliability score for the
+//! relay it involved, so flaky-but-known relays are used less. Entries that have
+//! been idle longer than a configurable TTL are expired, matching Veilid's
+//! roughly five-minute route caches.
+
+use crate::primitives::RouterId;
+
+use hashbrown::HashMap;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Exponential-decay factor applied on each recorded outcome.
+const DEFAULT_DECAY: f64 = 0.9;
+
+/// Default idle time-to-live for stats entries, in seconds (~5 minutes).
+const DEFAULT_TTL: u64 = 5 * 60;
+
+/// Recorded reliability state for a single relay.
+#[derive(Debug, Clone)]
+struct Entry {
+    /// Raw decaying score; normalized to `[0, 1]` by [`RelayStats::reliability`].
+    score: f64,
+
+    /// Unix timestamp of the last recorded outcome.
+    last_update: u64,
+}
+
+/// Per-relay reliability scoring with exponential decay and idle expiry.
+#[derive(Debug, Clone)]
+pub struct RelayStats {
+    /// Decay factor applied to the running score on each event.
+    decay: f64,
+
+    /// Idle time-to-live after which an entry is expired, in seconds.
+    ttl: u64,
+
+    /// Per-relay reliability entries.
+    entries: HashMap<RouterId, Entry>,
+}
+
+impl Default for RelayStats {
+    fn default() -> Self {
+        Self {
+            decay: DEFAULT_DECAY,
+            ttl: DEFAULT_TTL,
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl RelayStats {
+    /// Create a new stats tracker with an explicit decay factor and idle TTL.
+    pub fn new(decay: f64, ttl: u64) -> Self {
+        Self {
+            decay,
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Record the outcome of an event involving `id`.
+    ///
+    /// Updates the running score as `score = score * decay + outcome`, where
+    /// `outcome` is `1.0` on success and `0.0` on failure. Stale entries are
+    /// expired first so a relay that has been idle past the TTL starts fresh.
+    pub fn record_outcome(&mut self, id: RouterId, success: bool) {
+        let now = now_secs();
+        self.expire(now);
+
+        let decay = self.decay;
+        let entry = self.entries.entry(id).or_insert_with(|| Entry {
+            // New relays start at the steady-state success score so a freshly
+            // seen relay is given the benefit of the doubt.
+            score: 1.0 / (1.0 - decay),
+            last_update: now,
+        });
+
+        let outcome = if success { 1.0 } else { 0.0 };
+        entry.score = entry.score * decay + outcome;
+        entry.last_update = now;
+    }
+
+    /// Normalized reliability of `id` in `[0, 1]`.
+    ///
+    /// Relays with no recorded outcomes — or whose entry has expired — are given
+    /// the neutral value `1.0` so they are not penalized before they have been
+    /// observed.
+    pub fn reliability(&self, id: &RouterId) -> f64 {
+        let now = now_secs();
+        match self.entries.get(id) {
+            Some(entry) if now.saturating_sub(entry.last_update) < self.ttl => {
+                (entry.score * (1.0 - self.decay)).clamp(0.0, 1.0)
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Drop entries that have been idle longer than the configured TTL.
+    fn expire(&mut self, now: u64) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, entry| now.saturating_sub(entry.last_update) < ttl);
+    }
+}
+
+/// Current wall-clock time as seconds since the Unix epoch.
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_relay_is_neutral() {
+        let stats = RelayStats::default();
+        assert_eq!(stats.reliability(&RouterId::random()), 1.0);
+    }
+
+    #[test]
+    fn repeated_failures_lower_reliability() {
+        let mut stats = RelayStats::default();
+        let id = RouterId::random();
+
+        let baseline = {
+            stats.record_outcome(id, true);
+            stats.reliability(&id)
+        };
+        for _ in 0..10 {
+            stats.record_outcome(id, false);
+        }
+        assert!(stats.reliability(&id) < baseline);
+    }
+
+    #[test]
+    fn successes_keep_reliability_high() {
+        let mut stats = RelayStats::default();
+        let id = RouterId::random();
+        for _ in 0..10 {
+            stats.record_outcome(id, true);
+        }
+        assert!(stats.reliability(&id) > 0.9);
+    }
+
+    #[test]
+    fn expired_entries_reset_to_neutral() {
+        let mut stats = RelayStats::new(DEFAULT_DECAY, 0);
+        let id = RouterId::random();
+        stats.record_outcome(id, false);
+        // With a zero TTL the entry is immediately considered stale.
+        assert_eq!(stats.reliability(&id), 1.0);
+    }
+}